@@ -2,6 +2,9 @@ use std::path::PathBuf;
 
 #[derive(Debug, palc::Parser)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// specify a password, optional
     #[arg(long, short = 'p')]
     pub password: Option<String>,
@@ -10,6 +13,12 @@ pub struct Args {
     #[arg(long, short = 'Q')]
     pub qrcode_overlap: bool,
 
+    /// fail instead of warning when the embedded QR Code is not scannable
+    /// after being overlaid onto the image. With `--structured-append` no
+    /// scannability check is made, so this flag has no effect there.
+    #[arg(long)]
+    pub strict: bool,
+
     /// has quiet zone of QR Code
     ///
     /// can be: `true`/`false`
@@ -37,7 +46,87 @@ pub struct Args {
     #[arg(long, default_value = "ffffffff")]
     pub qrcode_bg_color: String,
 
+    /// embed a TOTP `otpauth://` provisioning URI instead of the raw
+    /// password, turning the merged image into a scannable 2FA enrollment
+    #[arg(long)]
+    pub otpauth: bool,
+
+    /// issuer (`otpauth` label/parameter), required with `--otpauth`
+    #[arg(long)]
+    pub issuer: Option<String>,
+
+    /// account name (`otpauth` label), required with `--otpauth`
+    #[arg(long)]
+    pub account: Option<String>,
+
+    /// TOTP hash algorithm, one of `SHA1`, `SHA256`, `SHA512`
+    #[arg(long, default_value = "SHA1")]
+    pub algorithm: String,
+
+    /// number of digits in a generated TOTP code
+    #[arg(long, default_value_t = 6)]
+    pub digits: u32,
+
+    /// validity period of a generated TOTP code, in seconds
+    #[arg(long, default_value_t = 30)]
+    pub period: u64,
+
+    /// split the QR payload across up to 16 tiled symbols using QR
+    /// structured append, for payloads too large to fit a single QR code
+    #[arg(long)]
+    pub structured_append: bool,
+
+    /// payload bytes carried by each symbol when `--structured-append` is set
+    #[arg(long, default_value_t = 64)]
+    pub symbol_capacity: usize,
+
+    /// QR Code error correction level, one of `l`, `m`, `q`, `h`
+    #[arg(long, default_value = "h")]
+    pub ec_level: String,
+
+    /// force a specific QR Code version (1-40) instead of the smallest one
+    /// that fits the payload
+    #[arg(long)]
+    pub qr_version: Option<i16>,
+
+    /// pixel size of a single QR Code module, overrides the auto-computed
+    /// overlay size. Ignored with `--structured-append`, whose symbols are
+    /// always sized to fit their tile.
+    #[arg(long)]
+    pub module_size: Option<u32>,
+
+    /// emit only the QR Code itself, as `png` or `svg`, instead of
+    /// overlaying it and packing a ZIP
+    #[arg(long)]
+    pub qr_only: Option<String>,
+
     /// target file. if enabled `qrcode_overlap, must be one of PNG, JPEG and WEBP.`
     pub img: PathBuf,
     pub path: Vec<PathBuf>,
 }
+
+/// Alternative operating modes, invoked as `embed-image <command> ...`.
+///
+/// When no subcommand is given, `Args` behaves as before: it merges `path`
+/// into an AES-encrypted ZIP, optionally overlaying a QR code onto `img`.
+#[derive(Debug, palc::Subcommand)]
+pub enum Command {
+    /// Recover the password from the embedded QR code (or `--password`) and
+    /// extract the ZIP archive appended to a merged image.
+    Extract(ExtractArgs),
+}
+
+#[derive(Debug, palc::Parser)]
+pub struct ExtractArgs {
+    /// password to use instead of scanning the image for a QR code
+    #[arg(long, short = 'p')]
+    pub password: Option<String>,
+
+    /// directory to extract the archive into, defaults to the image's
+    /// file stem suffixed with `_extracted`
+    #[arg(long, short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// the merged image produced by the default (embed) mode
+    pub img: PathBuf,
+}
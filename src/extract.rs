@@ -0,0 +1,103 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, Cursor, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use spdlog::info;
+
+use crate::structured_append;
+
+/// Scan a grayscale image for QR codes and return the decoded UTF-8
+/// payload. If the image carries a full set of structured-append symbols,
+/// they are reassembled (and parity-checked) into the original payload;
+/// otherwise the first successfully decoded symbol is returned as-is.
+pub fn decode_qr(gray: &image::GrayImage) -> Option<String> {
+    let mut prepared = rqrr::PreparedImage::prepare(gray.clone());
+    let grids = prepared.detect_grids();
+
+    let contents: Vec<String> = grids
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok())
+        .map(|(_meta, content)| content)
+        .collect();
+
+    structured_append::reassemble(&contents).or_else(|| contents.into_iter().next())
+}
+
+/// Recover the password embedded in the leading image of `img` (or use
+/// `password` if given) and unpack the trailing ZIP archive into `output`.
+pub fn extract(
+    img: impl AsRef<Path>,
+    password: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let img = img.as_ref();
+    let mut file = File::open(img)?;
+
+    let mut archive = zip::ZipArchive::new(&mut file)?;
+    let zip_offset = archive.offset();
+
+    let password = match password {
+        Some(password) => password,
+        None => {
+            if zip_offset == 0 {
+                return Err("no leading image to scan for a QR code, and no --password given".into());
+            }
+
+            info!("scanning embedded image for a QR code");
+
+            file.seek(SeekFrom::Start(0))?;
+            let mut image_bytes = vec![0u8; zip_offset as usize];
+            file.read_exact(&mut image_bytes)?;
+
+            let gray = image::io::Reader::new(Cursor::new(image_bytes))
+                .with_guessed_format()?
+                .decode()?
+                .to_luma8();
+
+            decode_qr(&gray)
+                .ok_or("could not find a readable QR code in the image, try --password")?
+        }
+    };
+
+    let output = output.unwrap_or_else(|| {
+        let stem = img
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "output".to_string());
+        PathBuf::from(stem + "_extracted")
+    });
+    fs::create_dir_all(&output)?;
+
+    info!("extracting {} entries to {}", archive.len(), output.display());
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index_decrypt(i, password.as_bytes())? {
+            Ok(entry) => entry,
+            Err(_) => return Err("incorrect password".into()),
+        };
+
+        let Some(entry_path) = entry.enclosed_name() else {
+            continue;
+        };
+        let out_path = output.join(entry_path);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut out_file = File::create(&out_path)?;
+        io::copy(&mut entry, &mut out_file)?;
+    }
+
+    info!("all tasks finished without any error.");
+
+    Ok(())
+}
@@ -1,75 +1,115 @@
+mod args;
+mod extract;
+mod overlay;
+mod qr_render;
+mod structured_append;
+mod totp;
+mod walk;
+
 use std::{
     borrow::Cow,
     error::Error,
     fs::{self, OpenOptions},
-    io::{BufReader, Write},
-    ops::Div,
-    path::{Path, PathBuf},
+    io::Write,
 };
 
-use image::{EncodableLayout, ImageEncoder as _, Rgba, codecs::png::PngEncoder, imageops::overlay};
-use qrencode::{EcLevel, QrCode};
+use args::{Args, Command};
+use rand::RngCore;
 use spdlog::{error, info, warn};
 use zip::write::FileOptions;
 
-#[derive(Debug, palc::Parser)]
-pub struct Args {
-    /// specify a password, optional
-    #[arg(long, short = 'p')]
-    password: Option<String>,
-
-    /// add an QRCode overlap for password
-    #[arg(long, short = 'Q')]
-    qrcode_overlap: bool,
-
-    /// has quiet zone of QR Code
-    ///
-    /// can be: `true`/`false`
-    ///
-    /// Quiet zone means the surrounding blank area
-    #[arg(long, short = 'q', default_value_t = true)]
-    has_quiet_zone: std::primitive::bool, // workaround: bypass `bool` match
-
-    /// Position of QR code.
-    ///
-    /// Can be one of `top-left` (default), `top-right`, `bottom-left`, `bottom-right`, `center`
-    ///
-    /// will fallback to default on invalid input.
-    #[arg(long, short = 'P')]
-    qr_position: Option<String>,
-
-    /// Color of QR Code foreground (the bar itself)
-    ///
-    /// format: CSS3 Color
-    #[arg(long, default_value = "#000000ff")]
-    qrcode_fg_color: String,
-    /// Color of QR Code background (The blank background)
-    ///
-    /// format: CSS3 Color
-    #[arg(long, default_value = "ffffffff")]
-    qrcode_bg_color: String,
-
-    /// target file. if enabled `qrcode_overlap, must be one of PNG, JPEG and WEBP.`
-    img: PathBuf,
-    path: Vec<PathBuf>,
-}
-
 fn main() -> Result<(), Box<dyn Error>> {
     let Args {
+        command,
         img,
         path,
-        password,
+        mut password,
         qr_position,
         qrcode_overlap,
+        strict,
         has_quiet_zone,
         qrcode_fg_color,
         qrcode_bg_color,
+        otpauth,
+        issuer,
+        account,
+        algorithm,
+        digits,
+        period,
+        structured_append,
+        symbol_capacity,
+        ec_level,
+        qr_version,
+        module_size,
+        qr_only,
     } = palc::Parser::parse();
 
+    if let Some(Command::Extract(extract_args)) = command {
+        return extract::extract(extract_args.img, extract_args.password, extract_args.output);
+    }
+
     if !img.exists() {
         error!("input image not existing!");
     }
 
+    let qr_text = if otpauth {
+        let issuer = issuer.ok_or("--otpauth requires --issuer")?;
+        let account = account.ok_or("--otpauth requires --account")?;
+        let algorithm: totp::Algorithm = algorithm.parse()?;
+
+        let secret = match password.as_deref() {
+            Some(password) => password.as_bytes().to_vec(),
+            None => {
+                let mut secret = vec![0u8; 20];
+                rand::thread_rng().fill_bytes(&mut secret);
+                let encoded = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &secret);
+                info!("generated TOTP secret, reusing it as the ZIP password: {encoded}");
+                password = Some(encoded);
+                secret
+            }
+        };
+
+        Some(totp::build_otpauth_uri(
+            &secret, &issuer, &account, algorithm, digits, period,
+        ))
+    } else {
+        password.clone()
+    };
+
+    let ec_level = qr_render::parse_ec_level(&ec_level)?;
+
+    if let Some(format) = qr_only.as_deref() {
+        let text =
+            qr_text.ok_or("--qr-only requires --password or --otpauth to know what to encode")?;
+
+        let output_fn = img
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "qrcode".to_string())
+            + "_qr."
+            + format;
+        let output = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&output_fn)?;
+
+        qr_render::write_qr_only(
+            output,
+            format,
+            &text,
+            ec_level,
+            qr_version,
+            module_size,
+            &qrcode_fg_color,
+            &qrcode_bg_color,
+            has_quiet_zone,
+        )?;
+
+        info!("wrote standalone QR Code to {output_fn}");
+        return Ok(());
+    }
+
     let output_fn = img
         .file_name()
         .unwrap()
@@ -94,64 +134,36 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     info!("reading source image");
 
-    if let Some(pass) = password.as_deref()
+    if let Some(text) = qr_text.as_deref()
         && qrcode_overlap
     {
-        let file = OpenOptions::new().read(true).create(false).open(img)?;
-        let bufreader = BufReader::new(file);
-
-        info!("start pixel converting");
-
-        let mut orig_image = image::io::Reader::new(bufreader)
-            .with_guessed_format()?
-            .decode()?
-            .to_rgba8(); // use RGBA8 to better save space
-
-        info!("start QR Code generation");
-
-        let orig_width = orig_image.width();
-        let orig_height = orig_image.height();
-        let pixel_len = orig_width.min(orig_height).div(3).max(200);
-
-        let fg_color = csscolorparser::parse(&qrcode_fg_color)?.to_rgba8();
-        let bg_color = csscolorparser::parse(&qrcode_bg_color)?.to_rgba8();
-        let qrcode_img = QrCode::with_error_correction_level(pass, EcLevel::H)?
-            .render::<image::Rgba<u8>>()
-            .max_dimensions(pixel_len, pixel_len)
-            .quiet_zone(has_quiet_zone)
-            .light_color(Rgba(bg_color))
-            .dark_color(Rgba(fg_color))
-            .build();
-        let real_pixel_len = qrcode_img.width();
-
-        let (x, y) = match qr_position.as_deref() {
-            Some("top-right") => (orig_width - real_pixel_len, 0),
-            Some("bottom-left") => (0, orig_height - real_pixel_len),
-            Some("bottom-right") => (orig_width - real_pixel_len, orig_height - real_pixel_len),
-            Some("center") => (
-                (orig_width - real_pixel_len) / 2,
-                (orig_height - real_pixel_len) / 2,
-            ),
-            Some(pos) => {
-                if pos != "top-left" {
-                    warn!("unknown position {pos}, falling back to top-left");
-                }
-                (0, 0)
-            }
-            _ => (0, 0),
-        };
-
-        info!("overlapping QR Code on original image");
-        overlay(&mut orig_image, &qrcode_img, x.into(), y.into());
-
-        info!("writing overlapped image");
-        let encoder = PngEncoder::new(&mut output);
-        encoder.write_image(
-            orig_image.as_bytes(),
-            orig_image.width(),
-            orig_image.height(),
-            image::ColorType::Rgba8,
-        )?;
+        if structured_append {
+            overlay::write_structured_overlayed_image(
+                &img,
+                &mut output,
+                has_quiet_zone,
+                qrcode_fg_color,
+                qrcode_bg_color,
+                text,
+                symbol_capacity,
+                ec_level,
+                qr_version,
+            )?;
+        } else {
+            overlay::write_overlayed_image(
+                &img,
+                &mut output,
+                has_quiet_zone,
+                qr_position,
+                qrcode_fg_color,
+                qrcode_bg_color,
+                text,
+                strict,
+                ec_level,
+                qr_version,
+                module_size,
+            )?;
+        }
     } else {
         if qrcode_overlap {
             warn!("QR Code overlap does nothing if did not specify a password");
@@ -177,7 +189,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut path_to_pack = Vec::new();
 
     for p in path {
-        visit_dirs_or_file(p, &mut path_to_pack)?;
+        walk::visit_dirs_or_file(p, &mut path_to_pack)?;
     }
 
     for path in path_to_pack {
@@ -194,27 +206,3 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
-
-fn visit_dirs_or_file(
-    path: impl AsRef<Path>,
-    append_to: &mut Vec<PathBuf>,
-) -> Result<(), Box<dyn Error>> {
-    let path = path.as_ref();
-    if path.is_file() {
-        append_to.push(path.to_path_buf());
-        return Ok(());
-    }
-
-    let dir = fs::read_dir(path)?;
-    for entry in dir.flatten() {
-        let path = entry.path();
-
-        if path.is_dir() {
-            visit_dirs_or_file(path, append_to)?;
-        } else if path.is_file() {
-            append_to.push(path);
-        }
-    }
-
-    Ok(())
-}
@@ -6,10 +6,18 @@ use std::{
     path::Path,
 };
 
-use image::{EncodableLayout, ImageEncoder as _, Rgba, codecs::png::PngEncoder, imageops::overlay};
-use qrencode::{EcLevel, QrCode};
+use image::{
+    EncodableLayout, ImageEncoder as _, Rgba, codecs::png::PngEncoder, imageops::crop,
+    imageops::overlay,
+};
+use qrencode::EcLevel;
 use spdlog::{info, warn};
 
+use crate::extract::decode_qr;
+use crate::qr_render::{build_qr, measure_capacity};
+use crate::structured_append;
+
+#[allow(clippy::too_many_arguments)]
 pub fn write_overlayed_image(
     img: impl AsRef<Path>,
     output: impl Write,
@@ -18,6 +26,10 @@ pub fn write_overlayed_image(
     qrcode_fg_color: impl Deref<Target = str>,
     qrcode_bg_color: impl Deref<Target = str>,
     text: impl AsRef<str>,
+    strict: bool,
+    ec_level: EcLevel,
+    qr_version: Option<i16>,
+    module_size: Option<u32>,
 ) -> Result<(), Box<dyn Error>> {
     let file = OpenOptions::new()
         .read(true)
@@ -40,15 +52,24 @@ pub fn write_overlayed_image(
 
     let fg_color = csscolorparser::parse(&qrcode_fg_color)?.to_rgba8();
     let bg_color = csscolorparser::parse(&qrcode_bg_color)?.to_rgba8();
-    let qrcode_img = QrCode::with_error_correction_level(text.as_ref(), EcLevel::H)?
+    let renderer = build_qr(text.as_ref(), ec_level, qr_version)?
         .render::<image::Rgba<u8>>()
-        .max_dimensions(pixel_len, pixel_len)
         .quiet_zone(has_quiet_zone)
         .light_color(Rgba(bg_color))
-        .dark_color(Rgba(fg_color))
-        .build();
+        .dark_color(Rgba(fg_color));
+    let qrcode_img = match module_size {
+        Some(module_size) => renderer.module_dimensions(module_size, module_size).build(),
+        None => renderer.max_dimensions(pixel_len, pixel_len).build(),
+    };
     let real_pixel_len = qrcode_img.width();
 
+    if real_pixel_len > orig_width || real_pixel_len > orig_height {
+        return Err(format!(
+            "rendered QR Code ({real_pixel_len}px) is larger than the source image ({orig_width}x{orig_height}px); use a smaller --module-size/--qr-version or a bigger source image"
+        )
+        .into());
+    }
+
     let (x, y) = match qr_position.as_deref() {
         Some("top-right") => (orig_width - real_pixel_len, 0),
         Some("bottom-left") => (0, orig_height - real_pixel_len),
@@ -69,6 +90,121 @@ pub fn write_overlayed_image(
     info!("overlapping QR Code on original image");
     overlay(&mut orig_image, &qrcode_img, x.into(), y.into());
 
+    info!("verifying embedded QR Code is still scannable");
+    let embedded_region = crop(&mut orig_image, x, y, real_pixel_len, real_pixel_len).to_image();
+    let decoded = decode_qr(&image::DynamicImage::ImageRgba8(embedded_region).to_luma8());
+
+    match decoded.as_deref() {
+        Some(decoded) if decoded == text.as_ref() => {}
+        Some(decoded) => {
+            let message = format!(
+                "embedded QR Code decodes to an unexpected payload ({decoded:?}), the overlay region may be too low-contrast"
+            );
+            if strict {
+                return Err(message.into());
+            }
+            warn!("{message}");
+        }
+        None => {
+            let message =
+                "embedded QR Code is not scannable, try an opaque background or a larger --module-size";
+            if strict {
+                return Err(message.into());
+            }
+            warn!("{message}");
+        }
+    }
+
+    info!("writing overlapped image");
+    let encoder = PngEncoder::new(output);
+    encoder.write_image(
+        orig_image.as_bytes(),
+        orig_image.width(),
+        orig_image.height(),
+        image::ColorType::Rgba8,
+    )?;
+
+    Ok(())
+}
+
+/// Split `text` across up to 16 structured-append QR symbols and tile them
+/// onto `img`, for payloads too large to fit a single QR code.
+#[allow(clippy::too_many_arguments)]
+pub fn write_structured_overlayed_image(
+    img: impl AsRef<Path>,
+    output: impl Write,
+    has_quiet_zone: bool,
+    qrcode_fg_color: impl Deref<Target = str>,
+    qrcode_bg_color: impl Deref<Target = str>,
+    text: impl AsRef<str>,
+    symbol_capacity: usize,
+    ec_level: EcLevel,
+    qr_version: Option<i16>,
+) -> Result<(), Box<dyn Error>> {
+    let file = OpenOptions::new()
+        .read(true)
+        .create(false)
+        .open(img.as_ref())?;
+    let bufreader = BufReader::new(file);
+
+    info!("start pixel converting");
+
+    let mut orig_image = image::io::Reader::new(bufreader)
+        .with_guessed_format()?
+        .decode()?
+        .to_rgba8(); // use RGBA8 to better save space
+
+    let orig_width = orig_image.width();
+    let orig_height = orig_image.height();
+
+    let budget =
+        measure_capacity(ec_level, qr_version).saturating_sub(structured_append::HEADER_OVERHEAD);
+    info!(
+        "each structured-append symbol fits up to {budget} payload bytes at ec-level {ec_level:?} \
+         (requested --symbol-capacity {symbol_capacity})"
+    );
+    if symbol_capacity > budget {
+        return Err(format!(
+            "--symbol-capacity {symbol_capacity} exceeds the {budget}-byte budget for a single QR symbol at this ec-level/version"
+        )
+        .into());
+    }
+
+    let symbols = structured_append::split(text.as_ref(), symbol_capacity)?;
+    info!(
+        "splitting payload across {} structured-append QR symbol(s), {symbol_capacity} bytes each",
+        symbols.len()
+    );
+
+    let columns = if symbols.len() <= 4 {
+        1
+    } else {
+        (symbols.len() as f64).sqrt().ceil() as u32
+    };
+    let tile_len = orig_width.min(orig_height).div(3).max(200) / columns.max(1);
+
+    let fg_color = csscolorparser::parse(&qrcode_fg_color)?.to_rgba8();
+    let bg_color = csscolorparser::parse(&qrcode_bg_color)?.to_rgba8();
+    let positions = structured_append::layout_positions(symbols.len(), orig_width, orig_height, tile_len);
+
+    for (symbol, (x, y)) in symbols.iter().zip(positions) {
+        info!(
+            "overlapping structured-append symbol {}/{}",
+            symbol.index + 1,
+            symbol.total
+        );
+
+        let qrcode_img = build_qr(symbol.encode(), ec_level, qr_version)?
+            .render::<image::Rgba<u8>>()
+            .max_dimensions(tile_len, tile_len)
+            .quiet_zone(has_quiet_zone)
+            .light_color(Rgba(bg_color))
+            .dark_color(Rgba(fg_color))
+            .build();
+
+        overlay(&mut orig_image, &qrcode_img, x.into(), y.into());
+    }
+
     info!("writing overlapped image");
     let encoder = PngEncoder::new(output);
     encoder.write_image(
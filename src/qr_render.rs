@@ -0,0 +1,137 @@
+use std::{error::Error, io::Write};
+
+use image::{EncodableLayout, ImageEncoder as _, Rgba, codecs::png::PngEncoder};
+use qrencode::{Color, EcLevel, QrCode, Version};
+
+/// Parse an `--ec-level` value (`l`, `m`, `q`, `h`, case-insensitive).
+pub fn parse_ec_level(level: &str) -> Result<EcLevel, Box<dyn Error>> {
+    match level.to_ascii_lowercase().as_str() {
+        "l" => Ok(EcLevel::L),
+        "m" => Ok(EcLevel::M),
+        "q" => Ok(EcLevel::Q),
+        "h" => Ok(EcLevel::H),
+        other => Err(format!("unknown EC level {other:?}, expected one of l, m, q, h").into()),
+    }
+}
+
+/// Build a QR code at the given error-correction level, optionally pinned
+/// to a fixed version (1-40) instead of the smallest version that fits.
+/// `data` may be text or raw bytes (e.g. a structured-append symbol).
+pub fn build_qr(
+    data: impl AsRef<[u8]>,
+    ec_level: EcLevel,
+    version: Option<i16>,
+) -> Result<QrCode, Box<dyn Error>> {
+    match version {
+        Some(version) => Ok(QrCode::with_version(
+            data,
+            Version::Normal(version),
+            ec_level,
+        )?),
+        None => Ok(QrCode::with_error_correction_level(data, ec_level)?),
+    }
+}
+
+/// Measure, by binary search, the largest byte-mode payload (in bytes) that
+/// still fits a single QR symbol at the given EC level/version. Used to
+/// report the real per-symbol budget instead of letting users discover it
+/// via a capacity error.
+pub fn measure_capacity(ec_level: EcLevel, version: Option<i16>) -> usize {
+    // 2953 bytes is the largest byte-mode capacity (version 40, EC level L).
+    // `0u8` isn't in the QR Numeric/Alphanumeric charsets, so the encoder is
+    // forced into Byte mode, same as the non-alphanumeric structured-append
+    // header/payload bytes this budget is actually measuring for.
+    let (mut lo, mut hi) = (0usize, 2953usize);
+    while lo < hi {
+        let mid = lo + (hi - lo + 1) / 2;
+        if build_qr(vec![0u8; mid], ec_level, version).is_ok() {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    lo
+}
+
+/// Standard QR quiet zone width, in modules.
+const QUIET_ZONE_MODULES: u32 = 4;
+
+/// Render the QR module matrix as a standalone SVG document, one `<rect>`
+/// per dark module, matching the PNG path's `--has-quiet-zone` handling.
+pub fn render_svg(
+    qr: &QrCode,
+    module_size: u32,
+    fg_color: &str,
+    bg_color: &str,
+    has_quiet_zone: bool,
+) -> String {
+    let width = qr.width();
+    let margin = if has_quiet_zone { QUIET_ZONE_MODULES } else { 0 };
+    let dimension = (width as u32 + margin * 2) * module_size;
+    let colors = qr.to_colors();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {dimension} {dimension}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"{bg_color}\"/>\n"
+    );
+
+    for (i, color) in colors.iter().enumerate() {
+        if *color == Color::Dark {
+            let x = ((i % width) as u32 + margin) * module_size;
+            let y = ((i / width) as u32 + margin) * module_size;
+            svg += &format!(
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{module_size}\" height=\"{module_size}\" fill=\"{fg_color}\"/>\n"
+            );
+        }
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+/// Write a standalone QR code (no image overlay) in the requested format,
+/// either `png` or `svg`.
+#[allow(clippy::too_many_arguments)]
+pub fn write_qr_only(
+    mut output: impl Write,
+    format: &str,
+    text: &str,
+    ec_level: EcLevel,
+    version: Option<i16>,
+    module_size: Option<u32>,
+    fg_color: &str,
+    bg_color: &str,
+    has_quiet_zone: bool,
+) -> Result<(), Box<dyn Error>> {
+    let qr = build_qr(text, ec_level, version)?;
+    let module_size = module_size.unwrap_or(8);
+
+    match format.to_ascii_lowercase().as_str() {
+        "svg" => {
+            let svg = render_svg(&qr, module_size, fg_color, bg_color, has_quiet_zone);
+            output.write_all(svg.as_bytes())?;
+        }
+        "png" => {
+            let fg = csscolorparser::parse(fg_color)?.to_rgba8();
+            let bg = csscolorparser::parse(bg_color)?.to_rgba8();
+            let image = qr
+                .render::<image::Rgba<u8>>()
+                .module_dimensions(module_size, module_size)
+                .quiet_zone(has_quiet_zone)
+                .light_color(Rgba(bg))
+                .dark_color(Rgba(fg))
+                .build();
+
+            let encoder = PngEncoder::new(output);
+            encoder.write_image(
+                image.as_bytes(),
+                image.width(),
+                image.height(),
+                image::ColorType::Rgba8,
+            )?;
+        }
+        other => return Err(format!("unknown --qr-only format {other:?}, expected png or svg").into()),
+    }
+
+    Ok(())
+}
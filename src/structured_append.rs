@@ -0,0 +1,177 @@
+use std::error::Error;
+
+use spdlog::warn;
+
+/// QR structured append supports at most 16 symbols per payload.
+pub const MAX_SYMBOLS: usize = 16;
+
+/// Worst-case byte length of [`Symbol::encode`]'s header (`SA` + two 2-digit
+/// counters + a 2-digit hex parity + two `.` separators and a `|`), used to size the
+/// per-symbol payload budget.
+pub const HEADER_OVERHEAD: usize = "SA99.99.FF|".len();
+
+/// One symbol of a structured-append payload: a small text header (0-based
+/// index, total symbol count, parity) followed by this symbol's slice of
+/// the original text. Kept as text, rather than raw bytes, so it survives
+/// the UTF-8 round trip through the QR decoder the same way a plain
+/// embedded password does.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub index: u8,
+    pub total: u8,
+    pub parity: u8,
+    pub payload: String,
+}
+
+impl Symbol {
+    const PREFIX: &'static str = "SA";
+
+    /// Header followed by the payload slice, ready to feed straight into
+    /// `QrCode::with_error_correction_level`.
+    pub fn encode(&self) -> String {
+        format!(
+            "{}{:02}.{:02}.{:02X}|{}",
+            Self::PREFIX,
+            self.index,
+            self.total,
+            self.parity,
+            self.payload
+        )
+    }
+
+    /// Parse a symbol back out of a decoded QR payload, returning `None` if
+    /// it isn't a structured-append symbol at all.
+    pub fn decode(text: &str) -> Option<Self> {
+        let rest = text.strip_prefix(Self::PREFIX)?;
+        let (header, payload) = rest.split_once('|')?;
+        let mut fields = header.split('.');
+        let index = fields.next()?.parse().ok()?;
+        let total = fields.next()?.parse().ok()?;
+        let parity = u8::from_str_radix(fields.next()?, 16).ok()?;
+
+        Some(Symbol {
+            index,
+            total,
+            parity,
+            payload: payload.to_string(),
+        })
+    }
+}
+
+/// Split `text` into up to [`MAX_SYMBOLS`] structured-append symbols, each
+/// carrying at most `symbol_capacity` bytes of payload (split on UTF-8 char
+/// boundaries so every symbol stays valid text; a single char wider than
+/// `symbol_capacity` gets a symbol of its own and exceeds it). The parity byte is the XOR
+/// over every byte of the original text, so a scanner can confirm all
+/// symbols were reassembled correctly.
+pub fn split(text: &str, symbol_capacity: usize) -> Result<Vec<Symbol>, Box<dyn Error>> {
+    if symbol_capacity == 0 {
+        return Err("symbol capacity must be greater than zero".into());
+    }
+
+    let parity = text.as_bytes().iter().fold(0u8, |acc, byte| acc ^ byte);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = (start + symbol_capacity).min(text.len());
+        while end > start && !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == start {
+            // a single char is wider than `symbol_capacity`, take it whole
+            end = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        chunks.push(&text[start..end]);
+        start = end;
+    }
+    if chunks.is_empty() {
+        chunks.push("");
+    }
+
+    if chunks.len() > MAX_SYMBOLS {
+        return Err(format!(
+            "payload needs {} symbols of {symbol_capacity} bytes each, but structured append supports at most {MAX_SYMBOLS}",
+            chunks.len()
+        )
+        .into());
+    }
+
+    let total = chunks.len() as u8;
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, payload)| Symbol {
+            index: index as u8,
+            total,
+            parity,
+            payload: payload.to_string(),
+        })
+        .collect())
+}
+
+/// Reassemble the original text out of decoded QR payloads, if (and only
+/// if) `contents` includes a full, parity-verified set of structured-append
+/// symbols. Returns `None` for plain (non structured-append) payloads, so
+/// callers can fall back to treating `contents` as ordinary QR text.
+pub fn reassemble(contents: &[String]) -> Option<String> {
+    let mut symbols: Vec<Symbol> = contents.iter().filter_map(|c| Symbol::decode(c)).collect();
+    if symbols.is_empty() {
+        return None;
+    }
+
+    let total = symbols[0].total;
+    symbols.retain(|symbol| symbol.total == total);
+    symbols.sort_by_key(|symbol| symbol.index);
+    symbols.dedup_by_key(|symbol| symbol.index);
+
+    if symbols.len() != total as usize {
+        warn!(
+            "found {}/{total} structured-append symbols, cannot reassemble the payload yet",
+            symbols.len()
+        );
+        return None;
+    }
+
+    let parity = symbols[0].parity;
+    let reassembled = symbols.into_iter().map(|symbol| symbol.payload).collect::<String>();
+
+    if reassembled.as_bytes().iter().fold(0u8, |acc, byte| acc ^ byte) != parity {
+        warn!("structured-append parity check failed, reassembled payload may be corrupt");
+        return None;
+    }
+
+    Some(reassembled)
+}
+
+/// Pixel offsets to tile `count` symbols of size `symbol_len` across an
+/// image. Up to 4 symbols reuse the corner positions already used by
+/// `--qr-position` (top-left, top-right, bottom-left, bottom-right);
+/// larger counts fall back to a roughly square grid.
+pub fn layout_positions(
+    count: usize,
+    orig_width: u32,
+    orig_height: u32,
+    symbol_len: u32,
+) -> Vec<(u32, u32)> {
+    if count <= 4 {
+        let corners = [
+            (0, 0),
+            (orig_width.saturating_sub(symbol_len), 0),
+            (0, orig_height.saturating_sub(symbol_len)),
+            (
+                orig_width.saturating_sub(symbol_len),
+                orig_height.saturating_sub(symbol_len),
+            ),
+        ];
+        return corners[..count].to_vec();
+    }
+
+    let columns = (count as f64).sqrt().ceil() as u32;
+    (0..count as u32)
+        .map(|i| {
+            let (col, row) = (i % columns, i / columns);
+            (col * symbol_len, row * symbol_len)
+        })
+        .collect()
+}
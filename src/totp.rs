@@ -0,0 +1,51 @@
+/// TOTP hash algorithm, as carried by the `algorithm` parameter of an
+/// `otpauth://` provisioning URI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    fn as_str(self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "SHA1" => Ok(Algorithm::Sha1),
+            "SHA256" => Ok(Algorithm::Sha256),
+            "SHA512" => Ok(Algorithm::Sha512),
+            other => Err(format!("unknown TOTP algorithm {other}, expected one of SHA1, SHA256, SHA512")),
+        }
+    }
+}
+
+/// Build an `otpauth://totp/...` provisioning URI, as consumed by
+/// authenticator apps, out of a raw secret and enrollment metadata.
+pub fn build_otpauth_uri(
+    secret: &[u8],
+    issuer: &str,
+    account: &str,
+    algorithm: Algorithm,
+    digits: u32,
+    period: u64,
+) -> String {
+    let secret = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, secret);
+    let issuer_enc = urlencoding::encode(issuer);
+    let account_enc = urlencoding::encode(account);
+
+    format!(
+        "otpauth://totp/{issuer_enc}:{account_enc}?secret={secret}&issuer={issuer_enc}&algorithm={}&digits={digits}&period={period}",
+        algorithm.as_str(),
+    )
+}